@@ -14,6 +14,36 @@
 //! [`modify()`](struct.ChangeTracker.html#method.modify) method of
 //! `ChangeTracker` and read using the `as_ref()` method from the `AsRef` trait.
 //!
+//! If you would rather mutate the value directly instead of going through a
+//! closure, and only notify listeners when the value actually changed, use
+//! [`as_tracked_mut()`](struct.ChangeTracker.html#method.as_tracked_mut) to
+//! get a [`Modifier`] guard instead.
+//!
+//! `get_changes()` buffers changes only up to its channel's capacity: once a
+//! receiver's channel is full, newer changes are dropped for that receiver
+//! (`modify()` does not wait for it to catch up). For a subscriber that only
+//! cares about the current value, use
+//! [`watch()`](struct.ChangeTracker.html#method.watch) to get a
+//! [`Watcher`] instead. A `Watcher` is lossy/coalescing: it always exposes
+//! the latest value and never blocks a writer, at the cost of not seeing
+//! every intermediate change.
+//!
+//! For large `T`, sending a full clone of the value before and after every
+//! change can be wasteful. If `T` implements [`Delta`],
+//! [`get_deltas()`](struct.ChangeTracker.html#method.get_deltas) can be used
+//! instead of `get_changes()` to subscribe to compact patches rather than
+//! full `(old_value, new_value)` pairs.
+//!
+//! If you would rather run an async action on each change than manage a
+//! stream yourself, register it with
+//! [`add_async_listener()`](struct.ChangeTracker.html#method.add_async_listener),
+//! which hands the callback's future to a caller-supplied `Spawn` rather
+//! than blocking `modify()` on it.
+//!
+//! For fallible edits, [`modify_result()`](struct.ChangeTracker.html#method.modify_result)
+//! rolls the value back and skips notifying listeners if the closure
+//! returns an error.
+//!
 //! ## Example
 //!
 //! In this example, the functionality of `ChangeTracker` is shown.
@@ -62,6 +92,10 @@
 #![deny(missing_docs)]
 
 use futures::channel::mpsc;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 /// Tracks changes to data. Notifies listeners via a `futures::Stream`.
@@ -80,6 +114,20 @@ use std::sync::{Arc, RwLock};
 pub struct ChangeTracker<T> {
     value: T,
     senders: Arc<RwLock<VecSender<T>>>,
+    watch_value: Arc<RwLock<T>>,
+    watch_senders: Arc<RwLock<Vec<mpsc::Sender<()>>>>,
+    #[allow(clippy::type_complexity)]
+    delta_notifiers: Arc<RwLock<Vec<Box<dyn FnMut(&T, &T) -> bool + Send + Sync>>>>,
+    async_listeners: Arc<RwLock<Vec<ListenerEntry<T>>>>,
+    next_listener_id: Arc<AtomicU64>,
+}
+
+struct ListenerEntry<T> {
+    id: u64,
+    #[allow(clippy::type_complexity)]
+    callback: Box<dyn Fn(T, T) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>,
+    #[allow(clippy::type_complexity)]
+    spawn: Box<dyn Fn(Pin<Box<dyn Future<Output = ()> + Send>>) + Send + Sync>,
 }
 
 type VecSender<T> = Vec<mpsc::Sender<(T, T)>>;
@@ -92,8 +140,13 @@ where
     /// of the data of type `T`.
     pub fn new(value: T) -> Self {
         Self {
+            watch_value: Arc::new(RwLock::new(value.clone())),
             value,
             senders: Arc::new(RwLock::new(Vec::new())),
+            watch_senders: Arc::new(RwLock::new(Vec::new())),
+            delta_notifiers: Arc::new(RwLock::new(Vec::new())),
+            async_listeners: Arc::new(RwLock::new(Vec::new())),
+            next_listener_id: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -110,6 +163,109 @@ where
         rx
     }
 
+    /// Returns a [`Watcher`] which always exposes the latest value.
+    ///
+    /// Unlike [`get_changes()`](Self::get_changes), which buffers
+    /// `(old, new)` pairs only up to its channel's capacity and drops newer
+    /// changes for a receiver whose channel is full, a `Watcher` is lossy in
+    /// the other way: it only ever exposes the most recent value, skipping
+    /// intermediate ones, and a slow or stalled watcher can never block a
+    /// writer. This is useful
+    /// when a subscriber only cares about "what is the current value" and
+    /// "has it changed since I last looked", rather than every intermediate
+    /// change.
+    pub fn watch(&self) -> Watcher<T> {
+        // Capacity 0: `mpsc::channel` reserves one extra guaranteed slot per
+        // sender regardless of the requested capacity, so this already
+        // allows exactly one buffered notification - enough to coalesce
+        // back-to-back changes without growing unboundedly.
+        let (tx, rx) = mpsc::channel(0);
+        let mut watch_senders = self.watch_senders.write().unwrap();
+        watch_senders.push(tx);
+        Watcher {
+            value: self.watch_value.clone(),
+            rx,
+        }
+    }
+
+    /// Returns a `futures::Stream` of [`Delta::Patch`] values, one per
+    /// change while its channel has room, instead of full
+    /// `(old_value, new_value)` pairs.
+    ///
+    /// Requires `T: Delta`. On each change, `modify()` computes
+    /// `T::diff(&orig, &new_value)` for this receiver and sends the resulting
+    /// patch instead of the whole value. The diff is computed separately for
+    /// every receiver returned by `get_deltas()`.
+    ///
+    /// The capacity of the underlying channel is specified with the
+    /// `capacity` argument. As with [`get_changes()`](Self::get_changes),
+    /// once the channel is full, newer patches are dropped for this receiver.
+    /// To remove a listener, drop the Receiver.
+    pub fn get_deltas(&self, capacity: usize) -> mpsc::Receiver<T::Patch>
+    where
+        T: Delta,
+    {
+        let (mut tx, rx) = mpsc::channel(capacity);
+        let mut delta_notifiers = self.delta_notifiers.write().unwrap();
+        delta_notifiers.push(Box::new(move |orig, new_value| {
+            let patch = T::diff(orig, new_value);
+            match tx.start_send(patch) {
+                Ok(_) => true,
+                Err(e) => {
+                    if e.is_disconnected() {
+                        tracing::trace!("receiver dropped");
+                        false
+                    } else {
+                        tracing::trace!("error on start_send: {e}");
+                        true
+                    }
+                }
+            }
+        }));
+        rx
+    }
+
+    /// Register an async callback to run on every change, instead of
+    /// polling a stream yourself.
+    ///
+    /// `callback` is invoked with clones of `(orig, new_value)` after each
+    /// change made via [`modify()`](Self::modify) (or
+    /// [`as_tracked_mut()`](Self::as_tracked_mut)), and its returned future
+    /// is handed to `spawn` to run in the background. `modify()` does not
+    /// wait for the callback to complete, and never blocks on it: blocking
+    /// here would panic whenever `modify()` is itself called from within the
+    /// executor driving `spawn` (e.g. the crate's own documented
+    /// `block_on`-wrapped usage), since most executors refuse to be entered
+    /// reentrantly.
+    ///
+    /// Returns an [`AsyncListenerHandle`]; dropping it removes the callback,
+    /// mirroring how dropping a `Receiver` removes a stream listener.
+    pub fn add_async_listener<C, F, S>(&mut self, spawn: &S, callback: C) -> AsyncListenerHandle<T>
+    where
+        C: Fn(T, T) -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+        S: futures::task::Spawn + Clone + Send + Sync + 'static,
+        T: 'static,
+    {
+        let id = self.next_listener_id.fetch_add(1, Ordering::Relaxed);
+        let spawn = spawn.clone();
+        let mut async_listeners = self.async_listeners.write().unwrap();
+        async_listeners.push(ListenerEntry {
+            id,
+            callback: Box::new(move |orig, new_value| Box::pin(callback(orig, new_value))),
+            spawn: Box::new(move |fut| {
+                use futures::task::SpawnExt;
+                if let Err(e) = spawn.spawn(fut) {
+                    tracing::trace!("failed to spawn async listener: {e}");
+                }
+            }),
+        });
+        AsyncListenerHandle {
+            id,
+            listeners: self.async_listeners.clone(),
+        }
+    }
+
     /// Modify the data value, notifying listeners upon change.
     pub fn modify<F>(&mut self, f: F)
     where
@@ -118,26 +274,155 @@ where
         let orig = self.value.clone();
         f(&mut self.value);
         let new_value = self.value.clone();
-        {
-            let mut senders = self.senders.write().unwrap();
-            let mut keep = vec![];
-            for mut on_changed_tx in senders.drain(0..) {
-                // TODO use .send() here?
-                match on_changed_tx.start_send((orig.clone(), new_value.clone())) {
-                    Ok(_) => {
+        self.notify_listeners(orig, new_value);
+    }
+
+    /// Modify the data value with a fallible closure.
+    ///
+    /// If `f` returns `Err(e)`, the value is restored to what it was before
+    /// the call, `e` is propagated, and no listeners are notified. If `f`
+    /// returns `Ok(())`, listeners are notified exactly as in
+    /// [`modify()`](Self::modify). This supports validated state
+    /// transitions where a rejected or partially-applied edit must neither
+    /// persist nor generate a spurious change event.
+    pub fn modify_result<F, E>(&mut self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&mut T) -> Result<(), E>,
+    {
+        let orig = self.value.clone();
+        match f(&mut self.value) {
+            Ok(()) => {
+                let new_value = self.value.clone();
+                self.notify_listeners(orig, new_value);
+                Ok(())
+            }
+            Err(e) => {
+                self.value = orig;
+                Err(e)
+            }
+        }
+    }
+
+    /// Send `(orig, new_value)` to every live listener, dropping any whose
+    /// receiver has been disconnected.
+    fn notify_listeners(&self, orig: T, new_value: T) {
+        let mut senders = self.senders.write().unwrap();
+        let mut keep = vec![];
+        for mut on_changed_tx in senders.drain(0..) {
+            // TODO use .send() here?
+            match on_changed_tx.start_send((orig.clone(), new_value.clone())) {
+                Ok(_) => {
+                    keep.push(on_changed_tx);
+                }
+                Err(e) => {
+                    if e.is_disconnected() {
+                        tracing::trace!("receiver dropped");
+                    } else {
+                        tracing::trace!("error on start_send: {e}");
                         keep.push(on_changed_tx);
                     }
-                    Err(e) => {
-                        if e.is_disconnected() {
-                            tracing::trace!("receiver dropped");
-                        } else {
-                            tracing::trace!("error on start_send: {e}");
-                            keep.push(on_changed_tx);
-                        }
+                }
+            }
+        }
+        senders.extend(keep);
+
+        {
+            let mut delta_notifiers = self.delta_notifiers.write().unwrap();
+            delta_notifiers.retain_mut(|notify| notify(&orig, &new_value));
+        }
+
+        {
+            let async_listeners = self.async_listeners.read().unwrap();
+            for entry in async_listeners.iter() {
+                let fut = (entry.callback)(orig.clone(), new_value.clone());
+                (entry.spawn)(fut);
+            }
+        }
+
+        *self.watch_value.write().unwrap() = new_value;
+        let mut watch_senders = self.watch_senders.write().unwrap();
+        let mut keep = vec![];
+        for mut on_changed_tx in watch_senders.drain(0..) {
+            match on_changed_tx.try_send(()) {
+                Ok(_) => {
+                    keep.push(on_changed_tx);
+                }
+                Err(e) => {
+                    if e.is_disconnected() {
+                        tracing::trace!("watcher dropped");
+                    } else {
+                        // Channel is full, meaning a notification is already
+                        // pending for this watcher. Lossy/coalescing: that
+                        // pending notification covers this change too.
+                        keep.push(on_changed_tx);
                     }
                 }
             }
-            senders.extend(keep);
+        }
+        watch_senders.extend(keep);
+    }
+}
+
+impl<T> ChangeTracker<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Get a RAII guard which allows mutating the tracked value directly.
+    ///
+    /// Unlike [`modify()`](Self::modify), which always notifies listeners,
+    /// the returned [`Modifier`] compares the value before and after
+    /// mutation when it is dropped and only notifies listeners if the value
+    /// actually changed. This avoids spurious `(old, new)` events for edits
+    /// that turn out to be no-ops.
+    pub fn as_tracked_mut(&mut self) -> Modifier<'_, T> {
+        let orig = self.value.clone();
+        Modifier {
+            tracker: self,
+            orig,
+        }
+    }
+}
+
+/// RAII guard returned by [`ChangeTracker::as_tracked_mut()`].
+///
+/// Dereferences (mutably) to the tracked value `T`. When dropped, listeners
+/// are notified only if the value actually changed while the guard was
+/// held.
+pub struct Modifier<'a, T>
+where
+    T: Clone + PartialEq,
+{
+    tracker: &'a mut ChangeTracker<T>,
+    orig: T,
+}
+
+impl<'a, T> Deref for Modifier<'a, T>
+where
+    T: Clone + PartialEq,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.tracker.value
+    }
+}
+
+impl<'a, T> DerefMut for Modifier<'a, T>
+where
+    T: Clone + PartialEq,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.tracker.value
+    }
+}
+
+impl<'a, T> Drop for Modifier<'a, T>
+where
+    T: Clone + PartialEq,
+{
+    fn drop(&mut self) {
+        if self.orig != self.tracker.value {
+            let new_value = self.tracker.value.clone();
+            self.tracker.notify_listeners(self.orig.clone(), new_value);
         }
     }
 }
@@ -147,3 +432,84 @@ impl<T> AsRef<T> for ChangeTracker<T> {
         &self.value
     }
 }
+
+/// A lossy, coalescing subscription to a [`ChangeTracker`]'s value.
+///
+/// Returned by [`ChangeTracker::watch()`]. Use [`borrow()`](Self::borrow) to
+/// cheaply read the current value, and [`changed()`](Self::changed) to wait
+/// until a newer value is available. If several changes happen between two
+/// calls to `changed()`, only the latest value is observed.
+pub struct Watcher<T> {
+    value: Arc<RwLock<T>>,
+    rx: mpsc::Receiver<()>,
+}
+
+impl<T> Watcher<T> {
+    /// Borrow the current value.
+    ///
+    /// This reflects the latest value written by the tracker, which may be
+    /// newer than the value observed by the last call to `changed()`.
+    pub fn borrow(&self) -> impl Deref<Target = T> + '_ {
+        self.value.read().unwrap()
+    }
+
+    /// Wait until the tracked value has changed since the last call to
+    /// `changed()` (or since this `Watcher` was created).
+    ///
+    /// Returns `Err(Closed)` if the `ChangeTracker` has been dropped.
+    pub async fn changed(&mut self) -> Result<(), Closed> {
+        use futures::stream::StreamExt;
+        match self.rx.next().await {
+            Some(()) => Ok(()),
+            None => Err(Closed { _private: () }),
+        }
+    }
+}
+
+/// Error returned by [`Watcher::changed()`] when the source `ChangeTracker`
+/// has been dropped.
+#[derive(Debug)]
+pub struct Closed {
+    _private: (),
+}
+
+impl std::fmt::Display for Closed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the ChangeTracker was dropped")
+    }
+}
+
+impl std::error::Error for Closed {}
+
+/// A type that can describe a change from one value to another as a compact
+/// patch, instead of a full `(old_value, new_value)` pair.
+///
+/// Implement this for large `T` so that
+/// [`ChangeTracker::get_deltas()`] can broadcast small edit descriptions
+/// (e.g. a replaced byte range plus its new content) rather than entire
+/// before/after clones.
+pub trait Delta: Sized {
+    /// The compact representation of a change from one value to another.
+    ///
+    /// Must be `Send + 'static` since patches are broadcast across an
+    /// `mpsc::Sender` that is itself stored in `ChangeTracker`.
+    type Patch: Send + 'static;
+
+    /// Compute the patch that transforms `old` into `new`.
+    fn diff(old: &Self, new: &Self) -> Self::Patch;
+}
+
+/// Handle returned by [`ChangeTracker::add_async_listener()`].
+///
+/// Dropping this handle removes the associated callback from the tracker.
+pub struct AsyncListenerHandle<T> {
+    id: u64,
+    listeners: Arc<RwLock<Vec<ListenerEntry<T>>>>,
+}
+
+impl<T> Drop for AsyncListenerHandle<T> {
+    fn drop(&mut self) {
+        let mut listeners = self.listeners.write().unwrap();
+        listeners.retain(|entry| entry.id != self.id);
+    }
+}
@@ -3,7 +3,7 @@ use std::{cell::RefCell, rc::Rc, sync::Arc};
 use futures::stream::StreamExt;
 use parking_lot::Mutex;
 
-use async_change_tracker::ChangeTracker;
+use async_change_tracker::{ChangeTracker, Delta};
 
 #[test]
 fn test_change_tracker() {
@@ -138,3 +138,153 @@ fn test_multithreaded_change_tracker() {
 
     assert!(data_store_arc.lock().as_ref().val == 124);
 }
+
+#[test]
+fn test_as_tracked_mut_suppresses_no_op_changes() {
+    #[derive(Clone, PartialEq, Debug)]
+    struct StoreType {
+        val: i32,
+    }
+
+    let mut change_tracker = ChangeTracker::new(StoreType { val: 123 });
+    let mut rx = change_tracker.get_changes(1);
+
+    // Write the same value back: nothing actually changed, so no
+    // notification should be sent.
+    {
+        let mut guard = change_tracker.as_tracked_mut();
+        guard.val = 123;
+    }
+
+    // Now make a real change via the guard.
+    {
+        let mut guard = change_tracker.as_tracked_mut();
+        guard.val = 124;
+    }
+
+    let (old_value, new_value) = futures::executor::block_on(rx.next()).unwrap();
+    assert_eq!(old_value.val, 123);
+    assert_eq!(new_value.val, 124);
+
+    // The no-op mutation above must not have queued a second notification.
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_watch() {
+    #[derive(Clone, PartialEq, Debug)]
+    struct StoreType {
+        val: i32,
+    }
+
+    let mut change_tracker = ChangeTracker::new(StoreType { val: 123 });
+    let mut watcher = change_tracker.watch();
+    assert_eq!(watcher.borrow().val, 123);
+
+    // Several changes before the watcher checks in: it should only ever
+    // see the latest value, and a single `changed()` covers all of them.
+    change_tracker.modify(|v| v.val += 1);
+    change_tracker.modify(|v| v.val += 1);
+
+    futures::executor::block_on(watcher.changed()).unwrap();
+    assert_eq!(watcher.borrow().val, 125);
+
+    drop(change_tracker);
+    assert!(futures::executor::block_on(watcher.changed()).is_err());
+}
+
+#[test]
+fn test_get_deltas() {
+    #[derive(Clone, PartialEq, Debug)]
+    struct StoreType {
+        val: i32,
+    }
+
+    impl Delta for StoreType {
+        type Patch = i32;
+
+        fn diff(old: &Self, new: &Self) -> i32 {
+            new.val - old.val
+        }
+    }
+
+    let mut change_tracker = ChangeTracker::new(StoreType { val: 123 });
+    let rx = change_tracker.get_deltas(1);
+
+    change_tracker.modify(|v| v.val += 1);
+
+    let check_change = rx.take(1).for_each(|patch| {
+        assert_eq!(patch, 1);
+        futures::future::ready(())
+    });
+    futures::executor::block_on(check_change);
+}
+
+#[test]
+fn test_add_async_listener() {
+    use futures::executor::ThreadPool;
+    use std::sync::mpsc as std_mpsc;
+    use std::time::Duration;
+
+    #[derive(Clone, PartialEq, Debug)]
+    struct StoreType {
+        val: i32,
+    }
+
+    let mut change_tracker = ChangeTracker::new(StoreType { val: 123 });
+    let pool = ThreadPool::new().unwrap();
+
+    let (done_tx, done_rx) = std_mpsc::channel();
+    let _handle = change_tracker.add_async_listener(&pool, move |old_value, new_value| {
+        let done_tx = done_tx.clone();
+        async move {
+            assert_eq!(old_value.val, 123);
+            assert_eq!(new_value.val, 124);
+            done_tx.send(()).unwrap();
+        }
+    });
+
+    // Calling modify() from within a future that is itself driven by
+    // block_on must not panic, even with an async listener registered: the
+    // listener's future runs on the thread pool, not on this executor.
+    let cause_change = async move {
+        change_tracker.modify(|v| v.val += 1);
+    };
+    futures::executor::block_on(cause_change);
+
+    done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+}
+
+#[test]
+fn test_modify_result() {
+    #[derive(Clone, PartialEq, Debug)]
+    struct StoreType {
+        val: i32,
+    }
+
+    let mut change_tracker = ChangeTracker::new(StoreType { val: 123 });
+    let rx = change_tracker.get_changes(1);
+
+    // A failing edit must be rolled back and must not notify listeners.
+    let result: Result<(), &'static str> = change_tracker.modify_result(|v| {
+        v.val += 1;
+        Err("rejected")
+    });
+    assert_eq!(result, Err("rejected"));
+    assert_eq!(change_tracker.as_ref().val, 123);
+
+    // A successful edit behaves like modify().
+    change_tracker
+        .modify_result::<_, &'static str>(|v| {
+            v.val += 1;
+            Ok(())
+        })
+        .unwrap();
+
+    let check_change = rx.take(1).for_each(|(old_value, new_value)| {
+        assert_eq!(old_value.val, 123);
+        assert_eq!(new_value.val, 124);
+        futures::future::ready(())
+    });
+    futures::executor::block_on(check_change);
+}